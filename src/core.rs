@@ -0,0 +1,716 @@
+//! Transport-agnostic checker logic.
+//!
+//! `check_site` drives the fetch → extract → hash → diff pipeline through
+//! the [`Fetcher`] and [`Store`] traits instead of `worker::Fetch`/`KvStore`
+//! directly, so it can run against an in-memory mock and a local HTTP
+//! fixture server in tests, with the real Workers-backed implementations
+//! living in `lib.rs`.
+
+use crate::diff::line_diff;
+use scraper::{Html, Selector};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+pub(crate) const KV_HASH_SUFFIX: &str = "hash";
+pub(crate) const KV_ETAG_SUFFIX: &str = "etag";
+pub(crate) const KV_LAST_MODIFIED_SUFFIX: &str = "last_modified";
+pub(crate) const KV_CONTENT_SUFFIX: &str = "content";
+pub(crate) const KV_BODY_SUFFIX: &str = "body";
+
+/// Max number of changed lines included in a notification's diff.
+pub(crate) const MAX_DIFF_LINES: usize = 15;
+
+/// Conditional-GET validators carried over from the previous successful fetch.
+#[derive(Default)]
+pub(crate) struct Conditional {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// Result of fetching a single page, abstracted away from any particular
+/// HTTP client so the checker logic doesn't care whether it's talking to
+/// `worker::Fetch` or a local test server.
+pub(crate) enum FetchResponse {
+    NotModified,
+    Ok {
+        body: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+    Error(String),
+}
+
+/// Fetches a single URL, sending `conditional`'s validators as
+/// `If-None-Match`/`If-Modified-Since` headers when present.
+#[async_trait::async_trait(?Send)]
+pub(crate) trait Fetcher {
+    async fn fetch(&self, url: &str, conditional: Conditional) -> FetchResponse;
+}
+
+/// Small async key/value store, abstracting over Workers KV.
+///
+/// `get`/`put` return `Result` rather than swallowing failures, so a KV
+/// outage or quota error surfaces as an observable checker error instead of
+/// silently behaving like a missing key.
+#[async_trait::async_trait(?Send)]
+pub(crate) trait Store {
+    async fn get(&self, key: &str) -> Result<Option<String>, String>;
+    async fn put(&self, key: &str, value: &str) -> Result<(), String>;
+}
+
+/// Outcome of checking a single exam-center page. `diff` holds a truncated
+/// line-level diff against the previous content, when one could be computed.
+pub(crate) enum SiteOutcome {
+    Has2026 { diff: Option<String> },
+    Changed { diff: Option<String> },
+    Unchanged,
+    Error(String),
+}
+
+/// Result of [`check_site`]: the page-hash outcome, plus the raw HTML body
+/// when one was fetched this run - `None` on a 304 or a fetch error (a
+/// non-200 status or a transport failure), since no body was read. The last body that *was* read is always kept in
+/// `store` under [`body_key`], so callers that need the markup on every run
+/// (e.g. link extraction) can fall back to that instead of skipping when
+/// this is `None`.
+pub(crate) struct SiteCheck {
+    pub outcome: SiteOutcome,
+    pub body: Option<String>,
+}
+
+/// Derive a per-URL KV key so each monitored page keeps its own hash/ETag
+/// state without colliding with the others.
+pub(crate) fn kv_key(url: &str, suffix: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(url.as_bytes());
+    format!("{}_{}", hex::encode(hasher.finalize()), suffix)
+}
+
+/// KV key holding the last HTML body fetched for `url`, refreshed on every
+/// 200 response regardless of whether the content hash changed - so link
+/// extraction still has markup to work with on a 304, when [`SiteCheck::body`]
+/// is `None`.
+pub(crate) fn body_key(url: &str) -> String {
+    kv_key(url, KV_BODY_SUFFIX)
+}
+
+/// Fetch and hash a single exam-center page through `fetcher`, updating its
+/// state in `store`.
+pub(crate) async fn check_site<F: Fetcher, S: Store>(
+    fetcher: &F,
+    store: &S,
+    url: &str,
+    content_selector: Option<&str>,
+) -> SiteCheck {
+    let hash_key = kv_key(url, KV_HASH_SUFFIX);
+    let etag_key = kv_key(url, KV_ETAG_SUFFIX);
+    let last_modified_key = kv_key(url, KV_LAST_MODIFIED_SUFFIX);
+    let content_key = kv_key(url, KV_CONTENT_SUFFIX);
+    let body_key = body_key(url);
+
+    // Send conditional headers from the last successful fetch so the server
+    // can reply 304 and we can skip re-downloading/re-hashing the page. A KV
+    // failure here just means we miss the chance for a conditional GET, so
+    // it's logged and treated like an absent validator rather than aborting.
+    let conditional = Conditional {
+        etag: get_logged(store, url, &etag_key).await,
+        last_modified: get_logged(store, url, &last_modified_key).await,
+    };
+
+    let (body, etag, last_modified) = match fetcher.fetch(url, conditional).await {
+        FetchResponse::NotModified => {
+            console_log(&format!("{}: HTTP 304 Not Modified - skipping hash check", url));
+            return SiteCheck {
+                outcome: SiteOutcome::Unchanged,
+                body: None,
+            };
+        }
+        FetchResponse::Error(status) => {
+            console_log(&format!("{}: fetch returned {}", url, status));
+            return SiteCheck {
+                outcome: SiteOutcome::Error(status),
+                body: None,
+            };
+        }
+        FetchResponse::Ok {
+            body,
+            etag,
+            last_modified,
+        } => (body, etag, last_modified),
+    };
+
+    // Keep the raw body around even when the content hash didn't change, so
+    // a 304 run still has markup to re-extract links from.
+    put_logged(store, url, &body_key, &body).await;
+
+    // Extract the text of the configured content region, skipping script/
+    // style/noscript/footer subtrees so analytics, GTM, and tracking pixels
+    // that vary between requests don't cause false positives.
+    let content_to_hash = extract_content(&body, content_selector);
+
+    let mut hasher = Sha256::new();
+    hasher.update(content_to_hash.as_bytes());
+    let content_hash = hex::encode(hasher.finalize());
+    console_log(&format!(
+        "{}: content length: {}, hash: {}",
+        url,
+        content_to_hash.len(),
+        content_hash
+    ));
+
+    // Check for 2026 in the extracted content (the selected region, or the
+    // whole document when no selector matched)
+    let has_2026 = content_to_hash.contains("2026");
+
+    // Get the previous hash. Unlike the conditional-GET validators, a KV
+    // failure here must not be treated as "no previous value" - that would
+    // report a false "page changed" on every KV outage instead of surfacing
+    // the outage itself.
+    let previous_hash = match store.get(&hash_key).await {
+        Ok(v) => v,
+        Err(e) => {
+            let msg = format!("KV get {} failed: {}", hash_key, e);
+            console_error(&format!("{}: {}", url, msg));
+            return SiteCheck {
+                outcome: SiteOutcome::Error(msg),
+                body: Some(body),
+            };
+        }
+    };
+
+    // Check if content changed
+    let content_changed = previous_hash.as_ref() != Some(&content_hash);
+
+    // Detailed logging for debugging false positives
+    if content_changed {
+        match previous_hash {
+            Some(ref prev_hash) => console_log(&format!("{}: HASH_CHANGED: {} -> {}", url, prev_hash, content_hash)),
+            None => console_log(&format!("{}: HASH_CHANGED: (no previous) -> {}", url, content_hash)),
+        }
+    } else {
+        console_log(&format!("{}: HASH_UNCHANGED: {}", url, content_hash));
+    }
+
+    // When the content changed, diff it against the previous run's content
+    // (stored separately from the hash) so the notification can say *what*
+    // changed. Skipped on the first run, since there's nothing to diff against.
+    let diff = if content_changed {
+        get_logged(store, url, &content_key)
+            .await
+            .map(|previous_content| line_diff(&previous_content, &content_to_hash, MAX_DIFF_LINES))
+    } else {
+        None
+    };
+
+    // Update stored hash and content if content changed
+    if content_changed {
+        put_logged(store, url, &hash_key, &content_hash).await;
+        put_logged(store, url, &content_key, &content_to_hash).await;
+    }
+
+    // Always refresh the conditional-GET validators to whatever this 200
+    // response offered, so the next run can try a cheaper conditional GET.
+    if let Some(ref etag) = etag {
+        put_logged(store, url, &etag_key, etag).await;
+    }
+    if let Some(ref last_modified) = last_modified {
+        put_logged(store, url, &last_modified_key, last_modified).await;
+    }
+
+    let outcome = if has_2026 {
+        SiteOutcome::Has2026 { diff }
+    } else if content_changed {
+        SiteOutcome::Changed { diff }
+    } else {
+        SiteOutcome::Unchanged
+    };
+
+    SiteCheck {
+        outcome,
+        body: Some(body),
+    }
+}
+
+/// Reads `key` from `store`, logging and falling back to `None` on a KV
+/// failure - for callers where treating a failure as "no previous value" is
+/// an acceptable degradation rather than a reportable error.
+async fn get_logged<S: Store>(store: &S, url: &str, key: &str) -> Option<String> {
+    match store.get(key).await {
+        Ok(v) => v,
+        Err(e) => {
+            console_error(&format!("{}: KV get {} failed: {}", url, key, e));
+            None
+        }
+    }
+}
+
+/// Writes `key`/`value` to `store`, logging rather than propagating a KV
+/// failure - the checker still has a useful result for this run even if a
+/// write to refresh cached state doesn't land.
+async fn put_logged<S: Store>(store: &S, url: &str, key: &str, value: &str) {
+    if let Err(e) = store.put(key, value).await {
+        console_error(&format!("{}: KV put {} failed: {}", url, key, e));
+    }
+}
+
+/// Block-level tags whose boundaries get an explicit line break in
+/// `extract_content`'s output, so line-level diffing doesn't depend on
+/// incidental whitespace text nodes surviving in the source markup.
+const BLOCK_TAGS: &[&str] = &[
+    "p", "div", "section", "article", "header", "footer", "main", "nav", "aside", "ul", "ol",
+    "li", "table", "tr", "td", "th", "thead", "tbody", "tfoot", "h1", "h2", "h3", "h4", "h5",
+    "h6", "blockquote", "pre", "br", "hr", "form", "fieldset", "dl", "dt", "dd", "figcaption",
+];
+
+/// Parse `html` into a real DOM and extract the text of the region matched
+/// by `selector` (falling back to the whole document when unset or invalid),
+/// with `<script>`, `<style>`, `<noscript>`, and `<footer>` subtrees removed.
+///
+/// Using a proper parser instead of substring scanning avoids being fooled
+/// by nested tags, attributes containing `>`, or the literal string
+/// `</script>` inside other markup, and `selector` lets the caller narrow
+/// the hash to just the exam-schedule region instead of the whole page.
+///
+/// A newline is inserted at each [`BLOCK_TAGS`] boundary so the result has
+/// real line breaks between, say, list items or table rows even when the
+/// source HTML is unminified-whitespace-free - `line_diff` splits on `'\n'`,
+/// so without this a single-word change inside dense markup would diff as
+/// one giant line covering the whole region.
+pub(crate) fn extract_content(html: &str, selector: Option<&str>) -> String {
+    let document = Html::parse_document(html);
+
+    let container = selector
+        .and_then(|s| Selector::parse(s).ok())
+        .and_then(|s| document.select(&s).next())
+        .unwrap_or_else(|| document.root_element());
+
+    let strip_selector = Selector::parse("script, style, noscript, footer").unwrap();
+    let stripped: HashSet<_> = container
+        .select(&strip_selector)
+        .flat_map(|el| el.descendants().map(|n| n.id()))
+        .collect();
+
+    let mut text = String::with_capacity(html.len());
+    for node in container.descendants() {
+        if stripped.contains(&node.id()) {
+            continue;
+        }
+        if let Some(el) = node.value().as_element() {
+            if BLOCK_TAGS.contains(&el.name()) {
+                text.push('\n');
+            }
+            continue;
+        }
+        if let Some(t) = node.value().as_text() {
+            text.push_str(t);
+            text.push(' ');
+        }
+    }
+
+    text
+}
+
+/// Build one ntfy message summarizing every monitored page's outcome.
+pub(crate) fn summarize(results: &[(String, SiteOutcome)]) -> String {
+    let mut changed = Vec::new();
+    let mut has_2026 = Vec::new();
+    let mut errored = Vec::new();
+    let mut unchanged_count = 0;
+
+    for (url, outcome) in results {
+        match outcome {
+            SiteOutcome::Has2026 { diff } => has_2026.push((url.as_str(), diff.as_deref())),
+            SiteOutcome::Changed { diff } => changed.push((url.as_str(), diff.as_deref())),
+            SiteOutcome::Unchanged => unchanged_count += 1,
+            SiteOutcome::Error(status) => errored.push(format!("{} ({})", url, status)),
+        }
+    }
+
+    if results.len() == 1 {
+        // Preserve the original single-site wording when there's nothing to summarize.
+        if let Some((url, outcome)) = results.first() {
+            return match outcome {
+                SiteOutcome::Has2026 { diff } => format!(
+                    "JLPT 2026 dates may have been announced! Check {}{}",
+                    url,
+                    format_diff(diff.as_deref())
+                ),
+                SiteOutcome::Changed { diff } => format!(
+                    "UCD JLPT page has been updated. Check {}{}",
+                    url,
+                    format_diff(diff.as_deref())
+                ),
+                SiteOutcome::Unchanged => "JLPT check complete - no changes detected.".to_string(),
+                SiteOutcome::Error(status) => format!("JLPT checker error: {} returned {}", url, status),
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    if !has_2026.is_empty() {
+        for (url, diff) in &has_2026 {
+            lines.push(format!("2026 dates may be announced: {}{}", url, format_diff(*diff)));
+        }
+    }
+    if !changed.is_empty() {
+        for (url, diff) in &changed {
+            lines.push(format!("Changed: {}{}", url, format_diff(*diff)));
+        }
+    }
+    if !errored.is_empty() {
+        lines.push(format!("Errored: {}", errored.join(", ")));
+    }
+    lines.push(format!("Unchanged: {} page(s)", unchanged_count));
+
+    lines.join("\n")
+}
+
+/// Render a site's diff (if any) as a notification-ready suffix.
+fn format_diff(diff: Option<&str>) -> String {
+    match diff {
+        Some(d) if !d.is_empty() => format!("\n{}", d),
+        _ => String::new(),
+    }
+}
+
+/// `console_log!` only exists in a `worker` event context, so the
+/// transport-agnostic checker logs through a plain function instead - this
+/// keeps `cfg(test)` runs on native targets from pulling in the wasm bindings.
+pub(crate) fn console_log(message: &str) {
+    #[cfg(target_arch = "wasm32")]
+    worker::console_log!("{}", message);
+    #[cfg(not(target_arch = "wasm32"))]
+    println!("{}", message);
+}
+
+/// Same as [`console_log`], for failures that should stand out (e.g. a KV
+/// error) without necessarily aborting the run.
+pub(crate) fn console_error(message: &str) {
+    #[cfg(target_arch = "wasm32")]
+    worker::console_error!("{}", message);
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}", message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Method, Request, Response, Server};
+
+    /// In-memory stand-in for Workers KV.
+    #[derive(Default)]
+    struct MockStore(RefCell<HashMap<String, String>>);
+
+    #[async_trait::async_trait(?Send)]
+    impl Store for MockStore {
+        async fn get(&self, key: &str) -> Result<Option<String>, String> {
+            Ok(self.0.borrow().get(key).cloned())
+        }
+
+        async fn put(&self, key: &str, value: &str) -> Result<(), String> {
+            self.0.borrow_mut().insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    /// Fetches URLs from a locally bound fixture server via `hyper`, honoring
+    /// conditional-GET headers the same way the real worker client does.
+    struct HttpFetcher {
+        base_url: String,
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl Fetcher for HttpFetcher {
+        async fn fetch(&self, path: &str, conditional: Conditional) -> FetchResponse {
+            let client = hyper::Client::new();
+            let mut builder = Request::builder()
+                .method(Method::GET)
+                .uri(format!("{}{}", self.base_url, path));
+            if let Some(etag) = &conditional.etag {
+                builder = builder.header("If-None-Match", etag);
+            }
+            if let Some(last_modified) = &conditional.last_modified {
+                builder = builder.header("If-Modified-Since", last_modified);
+            }
+            let request = builder.body(Body::empty()).unwrap();
+
+            let response = match client.request(request).await {
+                Ok(r) => r,
+                Err(e) => return FetchResponse::Error(e.to_string()),
+            };
+
+            match response.status().as_u16() {
+                304 => FetchResponse::NotModified,
+                200 => {
+                    let etag = response
+                        .headers()
+                        .get("ETag")
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string);
+                    let last_modified = response
+                        .headers()
+                        .get("Last-Modified")
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string);
+                    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+                    let body = String::from_utf8_lossy(&bytes).into_owned();
+                    FetchResponse::Ok {
+                        body,
+                        etag,
+                        last_modified,
+                    }
+                }
+                status => FetchResponse::Error(format!("HTTP {}", status)),
+            }
+        }
+    }
+
+    const BASELINE_PAGE: &str = "<html><body><main>JLPT December 2025 registration is open.</main></body></html>";
+    const CHANGED_PAGE: &str = "<html><body><main>JLPT December 2025 registration is now closed.</main></body></html>";
+    const PAGE_2026: &str = "<html><body><main>JLPT December 2026 registration is open.</main></body></html>";
+
+    // Two fetches of the "same" page, differing only in an analytics
+    // <script> block (e.g. a rotated tracking id) - regression coverage for
+    // extract_content false positives.
+    const PAGE_WITH_SCRIPT_V1: &str = "<html><head><script>gtag('config', 'UA-1111');</script></head><body><main>JLPT December 2025 registration is open.</main><footer>Visit count: 101</footer></body></html>";
+    const PAGE_WITH_SCRIPT_V2: &str = "<html><head><script>gtag('config', 'UA-2222');</script></head><body><main>JLPT December 2025 registration is open.</main><footer>Visit count: 204</footer></body></html>";
+
+    // A page with content outside the monitored region (a visit counter in
+    // <nav>) that changes between fetches, to exercise CONTENT_SELECTOR
+    // narrowing the hash to just `#schedule`.
+    const SELECTOR_PAGE_V1: &str = "<html><body><nav>Visitors: 100</nav><div id=\"schedule\">JLPT December 2025 registration is open.</div></body></html>";
+    const SELECTOR_PAGE_V2: &str = "<html><body><nav>Visitors: 200</nav><div id=\"schedule\">JLPT December 2025 registration is open.</div></body></html>";
+
+    /// Starts a local fixture server whose response is chosen per-request by
+    /// `responder`, and returns its base URL.
+    async fn spawn_server<Resp>(responder: Resp) -> String
+    where
+        Resp: Fn(&hyper::Request<Body>) -> Response<Body> + Send + Sync + 'static,
+    {
+        let responder = Arc::new(responder);
+        let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+        let make_svc = make_service_fn(move |_conn| {
+            let responder = responder.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let responder = responder.clone();
+                    async move { Ok::<_, Infallible>(responder(&req)) }
+                }))
+            }
+        });
+        let server = Server::bind(&addr).serve(make_svc);
+        let base_url = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        base_url
+    }
+
+    #[tokio::test]
+    async fn first_run_stores_hash_and_content() {
+        let base_url = spawn_server(|_req| Response::new(Body::from(BASELINE_PAGE))).await;
+        let fetcher = HttpFetcher { base_url };
+        let store = MockStore::default();
+
+        let result = check_site(&fetcher, &store, "/", None).await;
+        assert!(matches!(result.outcome, SiteOutcome::Changed { diff: None }));
+        assert!(store.get(&kv_key("/", KV_HASH_SUFFIX)).await.unwrap().is_some());
+        assert_eq!(
+            store.get(&kv_key("/", KV_CONTENT_SUFFIX)).await.unwrap().unwrap(),
+            extract_content(BASELINE_PAGE, None)
+        );
+    }
+
+    #[tokio::test]
+    async fn unchanged_page_reports_no_diff() {
+        let base_url = spawn_server(|_req| Response::new(Body::from(BASELINE_PAGE))).await;
+        let fetcher = HttpFetcher { base_url };
+        let store = MockStore::default();
+
+        check_site(&fetcher, &store, "/", None).await;
+        let result = check_site(&fetcher, &store, "/", None).await;
+        assert!(matches!(result.outcome, SiteOutcome::Unchanged));
+    }
+
+    #[tokio::test]
+    async fn changed_page_includes_diff() {
+        let calls = std::sync::atomic::AtomicU32::new(0);
+        let base_url = spawn_server(move |_req| {
+            let count = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if count == 0 {
+                Response::new(Body::from(BASELINE_PAGE))
+            } else {
+                Response::new(Body::from(CHANGED_PAGE))
+            }
+        })
+        .await;
+        let fetcher = HttpFetcher { base_url };
+        let store = MockStore::default();
+
+        check_site(&fetcher, &store, "/", None).await;
+        let result = check_site(&fetcher, &store, "/", None).await;
+        match result.outcome {
+            SiteOutcome::Changed { diff: Some(d) } => {
+                assert!(d.contains("closed"));
+                assert!(d.contains("open"));
+            }
+            _ => panic!("expected a changed outcome with a diff"),
+        }
+    }
+
+    #[tokio::test]
+    async fn page_with_2026_is_flagged() {
+        let base_url = spawn_server(|_req| Response::new(Body::from(PAGE_2026))).await;
+        let fetcher = HttpFetcher { base_url };
+        let store = MockStore::default();
+
+        let result = check_site(&fetcher, &store, "/", None).await;
+        assert!(matches!(result.outcome, SiteOutcome::Has2026 { .. }));
+    }
+
+    #[tokio::test]
+    async fn cloudfront_block_is_reported_as_error() {
+        let base_url = spawn_server(|_req| {
+            Response::builder()
+                .status(403)
+                .body(Body::from("blocked"))
+                .unwrap()
+        })
+        .await;
+        let fetcher = HttpFetcher { base_url };
+        let store = MockStore::default();
+
+        let result = check_site(&fetcher, &store, "/", None).await;
+        match result.outcome {
+            SiteOutcome::Error(status) => assert_eq!(status, "HTTP 403"),
+            _ => panic!("expected an error outcome"),
+        }
+    }
+
+    #[tokio::test]
+    async fn not_modified_short_circuits_without_rehashing() {
+        let base_url = spawn_server(|req| {
+            if req.headers().contains_key("If-None-Match") {
+                Response::builder().status(304).body(Body::empty()).unwrap()
+            } else {
+                Response::builder()
+                    .header("ETag", "\"v1\"")
+                    .body(Body::from(BASELINE_PAGE))
+                    .unwrap()
+            }
+        })
+        .await;
+        let fetcher = HttpFetcher { base_url };
+        let store = MockStore::default();
+
+        check_site(&fetcher, &store, "/", None).await;
+        let result = check_site(&fetcher, &store, "/", None).await;
+        assert!(matches!(result.outcome, SiteOutcome::Unchanged));
+    }
+
+    #[tokio::test]
+    async fn varying_script_and_footer_do_not_trigger_false_positive() {
+        let calls = std::sync::atomic::AtomicU32::new(0);
+        let base_url = spawn_server(move |_req| {
+            let count = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if count == 0 {
+                Response::new(Body::from(PAGE_WITH_SCRIPT_V1))
+            } else {
+                Response::new(Body::from(PAGE_WITH_SCRIPT_V2))
+            }
+        })
+        .await;
+        let fetcher = HttpFetcher { base_url };
+        let store = MockStore::default();
+
+        check_site(&fetcher, &store, "/", None).await;
+        let result = check_site(&fetcher, &store, "/", None).await;
+        assert!(matches!(result.outcome, SiteOutcome::Unchanged));
+    }
+
+    #[tokio::test]
+    async fn content_selector_ignores_changes_outside_the_selected_region() {
+        let calls = std::sync::atomic::AtomicU32::new(0);
+        let base_url = spawn_server(move |_req| {
+            let count = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if count == 0 {
+                Response::new(Body::from(SELECTOR_PAGE_V1))
+            } else {
+                Response::new(Body::from(SELECTOR_PAGE_V2))
+            }
+        })
+        .await;
+        let fetcher = HttpFetcher { base_url };
+        let store = MockStore::default();
+
+        check_site(&fetcher, &store, "/", Some("#schedule")).await;
+        let result = check_site(&fetcher, &store, "/", Some("#schedule")).await;
+        assert!(matches!(result.outcome, SiteOutcome::Unchanged));
+    }
+
+    #[tokio::test]
+    async fn without_selector_the_same_nav_change_is_seen_as_changed() {
+        let calls = std::sync::atomic::AtomicU32::new(0);
+        let base_url = spawn_server(move |_req| {
+            let count = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if count == 0 {
+                Response::new(Body::from(SELECTOR_PAGE_V1))
+            } else {
+                Response::new(Body::from(SELECTOR_PAGE_V2))
+            }
+        })
+        .await;
+        let fetcher = HttpFetcher { base_url };
+        let store = MockStore::default();
+
+        check_site(&fetcher, &store, "/", None).await;
+        let result = check_site(&fetcher, &store, "/", None).await;
+        assert!(matches!(result.outcome, SiteOutcome::Changed { .. }));
+    }
+
+    #[test]
+    fn summarize_single_site_uses_the_original_wording() {
+        let results = vec![(
+            "https://www.ucd.ie/japan/exams/".to_string(),
+            SiteOutcome::Has2026 { diff: Some("+ December 2026".to_string()) },
+        )];
+        let message = summarize(&results);
+        assert_eq!(
+            message,
+            "JLPT 2026 dates may have been announced! Check https://www.ucd.ie/japan/exams/\n+ December 2026"
+        );
+    }
+
+    #[test]
+    fn summarize_multi_site_groups_by_outcome() {
+        let results = vec![
+            ("https://a.example/".to_string(), SiteOutcome::Has2026 { diff: None }),
+            ("https://b.example/".to_string(), SiteOutcome::Changed { diff: Some("+ new".to_string()) }),
+            ("https://c.example/".to_string(), SiteOutcome::Error("HTTP 403".to_string())),
+            ("https://d.example/".to_string(), SiteOutcome::Unchanged),
+            ("https://e.example/".to_string(), SiteOutcome::Unchanged),
+        ];
+        let message = summarize(&results);
+        assert_eq!(
+            message,
+            "2026 dates may be announced: https://a.example/\n\
+             Changed: https://b.example/\n+ new\n\
+             Errored: https://c.example/ (HTTP 403)\n\
+             Unchanged: 2 page(s)"
+        );
+    }
+
+    #[test]
+    fn summarize_with_all_unchanged() {
+        let results = vec![
+            ("https://a.example/".to_string(), SiteOutcome::Unchanged),
+            ("https://b.example/".to_string(), SiteOutcome::Unchanged),
+        ];
+        assert_eq!(summarize(&results), "Unchanged: 2 page(s)");
+    }
+}
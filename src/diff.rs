@@ -0,0 +1,75 @@
+//! Line-level diff between two text blobs, used to describe *what* changed
+//! on a monitored page instead of just reporting that it changed.
+
+/// Classic LCS dynamic-programming diff. Splits `old` and `new` into lines,
+/// aligns them on their longest common subsequence, and renders the result
+/// as unified-diff-style `-`/`+` lines (matched lines are omitted). The
+/// output is truncated to the first `max_lines` changed lines so it fits in
+/// a single notification.
+pub(crate) fn line_diff(old: &str, new: &str, max_lines: usize) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let (n, m) = (a.len(), b.len());
+
+    // dp[i][j] = length of the LCS of a[i..] and b[j..]
+    let mut dp = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            dp[i][j] = if a[i] == b[j] {
+                dp[i + 1][j + 1] + 1
+            } else {
+                dp[i + 1][j].max(dp[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while lines.len() < max_lines && (i < n || j < m) {
+        if i < n && j < m && a[i] == b[j] {
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || dp[i + 1][j] <= dp[i][j + 1]) {
+            lines.push(format!("+ {}", b[j]));
+            j += 1;
+        } else {
+            lines.push(format!("- {}", a[i]));
+            i += 1;
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_input_produces_no_diff() {
+        assert_eq!(line_diff("a\nb\nc", "a\nb\nc", 15), "");
+    }
+
+    #[test]
+    fn empty_input_produces_no_diff() {
+        assert_eq!(line_diff("", "", 15), "");
+    }
+
+    #[test]
+    fn pure_addition() {
+        assert_eq!(line_diff("a\nb", "a\nb\nc", 15), "+ c");
+    }
+
+    #[test]
+    fn pure_removal() {
+        assert_eq!(line_diff("a\nb\nc", "a\nb", 15), "- c");
+    }
+
+    #[test]
+    fn truncates_to_max_lines() {
+        let old = "";
+        let new = "1\n2\n3\n4\n5";
+        let diff = line_diff(old, new, 3);
+        assert_eq!(diff, "+ 1\n+ 2\n+ 3");
+    }
+}
@@ -1,142 +1,194 @@
-use sha2::{Sha256, Digest};
+mod core;
+mod diff;
+mod links;
+
+use crate::core::{body_key, check_site, summarize, Conditional, FetchResponse, SiteOutcome, Store};
+use crate::links::{check_links, extract_links, format_link_report, LinkChecker, LinkOutcome};
+use futures::future::join_all;
 use worker::*;
 
 const UCD_URL: &str = "https://www.ucd.ie/japan/exams/";
-const KV_KEY: &str = "page_content_hash";
+
+/// Max number of exam-center pages fetched at once, to stay within the
+/// worker's subrequest budget.
+const CONCURRENCY_LIMIT: usize = 5;
 
 /// Entry point for scheduled (cron) events
 #[event(scheduled)]
 async fn scheduled(_event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
     console_log!("JLPT checker running...");
 
-    if let Err(e) = check_jlpt_page(&env).await {
-        console_error!("Error checking JLPT page: {:?}", e);
+    if let Err(e) = check_jlpt_pages(&env).await {
+        console_error!("Error checking JLPT pages: {:?}", e);
         let _ = send_ntfy_notification(&env, &format!("JLPT checker error: {}", e)).await;
     }
 }
 
-async fn check_jlpt_page(env: &Env) -> Result<()> {
-    // Fetch the UCD JLPT page with a browser User-Agent
-    // (CloudFront blocks requests without one)
+/// Read the exam-center URLs to monitor from `WATCH_URLS` (comma-separated),
+/// falling back to the single UCD page when unset.
+fn watch_urls(env: &Env) -> Vec<String> {
+    match env.var("WATCH_URLS") {
+        Ok(v) => v
+            .to_string()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Err(_) => vec![UCD_URL.to_string()],
+    }
+}
+
+async fn check_jlpt_pages(env: &Env) -> Result<()> {
+    let fetcher = WorkerFetcher;
+    let store = WorkerStore(env.kv("PAGE_STATE")?);
+    let link_checker = WorkerLinkChecker;
+    let urls = watch_urls(env);
+    let content_selector = env.var("CONTENT_SELECTOR").ok().map(|v| v.to_string());
+
+    let mut results: Vec<(String, SiteOutcome)> = Vec::with_capacity(urls.len());
+    let mut link_reports: Vec<String> = Vec::new();
+    for chunk in urls.chunks(CONCURRENCY_LIMIT) {
+        let checks = chunk
+            .iter()
+            .map(|url| check_site(&fetcher, &store, url, content_selector.as_deref()));
+        let chunk_results = join_all(checks).await;
+        for (url, site_check) in chunk.iter().zip(chunk_results) {
+            // Link health should be checked every run, not just when the
+            // page hash changed - a link can die while the markup is
+            // byte-identical. On a 304 `site_check.body` is `None`, so fall
+            // back to the body `check_site` last stored for this URL.
+            let body = match &site_check.body {
+                Some(body) => Some(body.clone()),
+                None => match store.get(&body_key(url)).await {
+                    Ok(v) => v,
+                    Err(e) => {
+                        console_error!("{}: KV get {} failed: {}", url, body_key(url), e);
+                        None
+                    }
+                },
+            };
+            if let Some(body) = body {
+                let page_links = extract_links(&body, url);
+                let report = check_links(&link_checker, &store, url, &page_links).await;
+                if let Some(line) = format_link_report(url, &report) {
+                    link_reports.push(line);
+                }
+            }
+            results.push((url.clone(), site_check.outcome));
+        }
+    }
+
+    let mut message = summarize(&results);
+    if !link_reports.is_empty() {
+        message.push('\n');
+        message.push_str(&link_reports.join("\n"));
+    }
+    console_log!("{}", message);
+    send_ntfy_notification(env, &message).await?;
+
+    Ok(())
+}
+
+/// Fetches a page with `worker::Fetch`, the real [`core::Fetcher`] the
+/// scheduled handler runs against (tests use an in-process HTTP fixture
+/// server instead - see `core::tests`).
+struct WorkerFetcher;
+
+#[async_trait::async_trait(?Send)]
+impl core::Fetcher for WorkerFetcher {
+    async fn fetch(&self, url: &str, conditional: Conditional) -> FetchResponse {
+        match fetch_page(url, conditional).await {
+            Ok(response) => response,
+            Err(e) => FetchResponse::Error(e.to_string()),
+        }
+    }
+}
+
+async fn fetch_page(url: &str, conditional: Conditional) -> Result<FetchResponse> {
+    // Fetch the page with a browser User-Agent (CloudFront blocks requests
+    // without one).
     let headers = Headers::new();
     headers.set("User-Agent", "Mozilla/5.0 (compatible; JLPT-Checker/1.0)")?;
+    if let Some(ref etag) = conditional.etag {
+        headers.set("If-None-Match", etag)?;
+    }
+    if let Some(ref last_modified) = conditional.last_modified {
+        headers.set("If-Modified-Since", last_modified)?;
+    }
 
     let mut init = RequestInit::new();
     init.with_headers(headers);
 
-    let request = Request::new_with_init(UCD_URL, &init)?;
+    let request = Request::new_with_init(url, &init)?;
     let mut response = Fetch::Request(request).send().await?;
 
     let status = response.status_code();
+    if status == 304 {
+        return Ok(FetchResponse::NotModified);
+    }
     if status != 200 {
-        console_error!("Fetch returned HTTP {}", status);
-        return Err(Error::RustError(format!("HTTP {} from UCD page", status)));
+        return Ok(FetchResponse::Error(format!("HTTP {}", status)));
     }
 
+    // Remember the validators this response offered, if any, so the next
+    // run can fall back to a conditional GET.
+    let etag = response.headers().get("ETag")?;
+    let last_modified = response.headers().get("Last-Modified")?;
     let body = response.text().await?;
 
-    // Strip <script>, <style>, and <noscript> blocks to avoid false positives
-    // from analytics, GTM, tracking pixels, or injected CSS that vary between requests.
-    // The UCD page has no <main> tag, so we strip dynamic elements instead.
-    let content_to_hash = strip_dynamic_elements(&body);
-
-    let mut hasher = Sha256::new();
-    hasher.update(content_to_hash.as_bytes());
-    let content_hash = hex::encode(hasher.finalize());
-    console_log!("Content length: {}, hash: {}", content_to_hash.len(), content_hash);
-
-    // Check for 2026 content in main section only
-    let has_2026 = content_to_hash.contains("2026");
-
-    // Get the KV namespace
-    let kv = env.kv("PAGE_STATE")?;
-
-    // Get the previous hash
-    let previous_hash = kv.get(KV_KEY).text().await?;
+    Ok(FetchResponse::Ok {
+        body,
+        etag,
+        last_modified,
+    })
+}
 
-    // Check if content changed
-    let content_changed = previous_hash.as_ref() != Some(&content_hash);
+/// Stores checker state in Workers KV, the real [`core::Store`] the
+/// scheduled handler runs against (tests use an in-memory mock instead).
+struct WorkerStore(KvStore);
 
-    // Detailed logging for debugging false positives
-    if content_changed {
-        if let Some(ref prev_hash) = previous_hash {
-            console_log!("HASH_CHANGED: {} -> {}", prev_hash, content_hash);
-            // Store both hashes in KV for debugging
-            let _ = kv.put("previous_hash_debug", prev_hash)?.execute().await;
-            let _ = kv.put("current_hash_debug", &content_hash)?.execute().await;
-            let _ = kv.put("last_change_timestamp", &Date::now().to_string())?.execute().await;
-        } else {
-            console_log!("HASH_CHANGED: (no previous) -> {}", content_hash);
-        }
-    } else {
-        console_log!("HASH_UNCHANGED: {}", content_hash);
+#[async_trait::async_trait(?Send)]
+impl Store for WorkerStore {
+    async fn get(&self, key: &str) -> std::result::Result<Option<String>, String> {
+        self.0.get(key).text().await.map_err(|e| e.to_string())
     }
 
-    // Build notification message based on what we found
-    let message = if has_2026 {
-        "JLPT 2026 dates may have been announced! Check https://www.ucd.ie/japan/exams/"
-    } else if content_changed {
-        "UCD JLPT page has been updated. Check https://www.ucd.ie/japan/exams/"
-    } else {
-        "JLPT check complete - no changes detected."
-    };
-
-    console_log!("{}", message);
-
-    send_ntfy_notification(env, message).await?;
-
-    // Update stored hash if content changed
-    if content_changed {
-        kv.put(KV_KEY, &content_hash)?.execute().await?;
+    async fn put(&self, key: &str, value: &str) -> std::result::Result<(), String> {
+        let put = self.0.put(key, value).map_err(|e| e.to_string())?;
+        put.execute().await.map_err(|e| e.to_string())
     }
-
-    Ok(())
 }
 
-/// Remove `<script>`, `<style>`, and `<noscript>` blocks (and HTML comments)
-/// so the hash only covers visible page content.
-fn strip_dynamic_elements(html: &str) -> String {
-    let mut result = String::with_capacity(html.len());
-    let mut remaining = html;
-
-    while !remaining.is_empty() {
-        // Find the next tag to strip
-        let next_strip = [
-            ("<!--", "-->"),
-            ("<script", "</script>"),
-            ("<style", "</style>"),
-            ("<noscript", "</noscript>"),
-            ("<footer", "</footer>"),
-        ]
-        .iter()
-        .filter_map(|(open, close)| {
-            remaining
-                .to_ascii_lowercase()
-                .find(open)
-                .map(|pos| (pos, *open, *close))
-        })
-        .min_by_key(|(pos, _, _)| *pos);
-
-        match next_strip {
-            Some((pos, _open, close)) => {
-                result.push_str(&remaining[..pos]);
-                // Find the closing tag (case-insensitive)
-                let after_open = &remaining[pos..];
-                if let Some(end) = after_open.to_ascii_lowercase().find(close) {
-                    remaining = &after_open[end + close.len()..];
-                } else {
-                    // No closing tag found â€” skip the rest
-                    break;
-                }
-            }
-            None => {
-                result.push_str(remaining);
-                break;
-            }
+/// Probes registration links with `worker::Fetch`, the real
+/// [`links::LinkChecker`] the scheduled handler runs against.
+struct WorkerLinkChecker;
+
+#[async_trait::async_trait(?Send)]
+impl LinkChecker for WorkerLinkChecker {
+    async fn check(&self, url: &str) -> LinkOutcome {
+        let mut init = RequestInit::new();
+        init.with_method(Method::Head);
+
+        let request = match Request::new_with_init(url, &init) {
+            Ok(r) => r,
+            Err(e) => return LinkOutcome::TransportError(e.to_string()),
+        };
+
+        let response = match Fetch::Request(request).send().await {
+            Ok(r) => r,
+            Err(e) => return LinkOutcome::TransportError(e.to_string()),
+        };
+
+        // `fetch` follows redirects itself, so `response.url()` reflects
+        // wherever the link actually ended up.
+        let location = response.url().map(|u| u.to_string()).unwrap_or_else(|_| url.to_string());
+        let status = response.status_code();
+        if (400..600).contains(&status) {
+            LinkOutcome::HttpError { status, location }
+        } else {
+            LinkOutcome::Ok { status, location }
         }
     }
-
-    result
 }
 
 async fn send_ntfy_notification(env: &Env, message: &str) -> Result<()> {
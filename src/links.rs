@@ -0,0 +1,336 @@
+//! Discovers and validates the registration links on a monitored page,
+//! independently of the page-hash check in `core` - a dead or newly-added
+//! link is often the actual event users care about, ahead of a whole-page
+//! hash change.
+
+use crate::core::{console_error, kv_key, Store};
+use futures::future::join_all;
+use scraper::{Html, Selector};
+use std::collections::HashSet;
+use url::Url;
+
+const KV_LINKS_SUFFIX: &str = "links";
+const KV_BROKEN_LINKS_SUFFIX: &str = "broken_links";
+
+/// Max number of links checked at once per page, so a page with many links
+/// doesn't exhaust the worker's subrequest budget.
+const LINK_CONCURRENCY_LIMIT: usize = 5;
+
+/// Outcome of probing a single link.
+#[derive(Clone)]
+pub(crate) enum LinkOutcome {
+    /// Resolved successfully, possibly after following redirects.
+    Ok { status: u16, location: String },
+    /// The server responded, but with a 4xx/5xx.
+    HttpError { status: u16, location: String },
+    /// The request itself failed (DNS, TLS, timeout, ...).
+    TransportError(String),
+}
+
+/// Probes a single link, following redirects and reporting the final
+/// location and status.
+#[async_trait::async_trait(?Send)]
+pub(crate) trait LinkChecker {
+    async fn check(&self, url: &str) -> LinkOutcome;
+}
+
+/// What changed about a page's registration links since the last run.
+///
+/// `broken`/`fixed` are edge-triggered: a link that's still down from the
+/// previous run is omitted from `broken` (it was already reported), and only
+/// reappears once, in `fixed`, on the run where it starts working again.
+#[derive(Default)]
+pub(crate) struct LinkReport {
+    pub broken: Vec<(String, String)>,
+    pub new_links: Vec<String>,
+    pub fixed: Vec<String>,
+}
+
+/// Extract every `<a href>` on `html` that resolves (against `base_url`) to an
+/// http(s) URL, deduped and in document order.
+pub(crate) fn extract_links(html: &str, base_url: &str) -> Vec<String> {
+    let base = match Url::parse(base_url) {
+        Ok(u) => u,
+        Err(_) => return Vec::new(),
+    };
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("a[href]").unwrap();
+
+    let mut seen = HashSet::new();
+    let mut links = Vec::new();
+    for element in document.select(&selector) {
+        let Some(href) = element.value().attr("href") else {
+            continue;
+        };
+        let Ok(resolved) = base.join(href) else {
+            continue;
+        };
+        // `mailto:`/`tel:`/`javascript:` etc. are valid absolute URIs that
+        // `Url::join` happily resolves, but `WorkerLinkChecker` can only
+        // dispatch http(s) requests - anything else would fail every run
+        // and get reported as permanently broken.
+        if resolved.scheme() != "http" && resolved.scheme() != "https" {
+            continue;
+        }
+        let resolved = resolved.to_string();
+        if seen.insert(resolved.clone()) {
+            links.push(resolved);
+        }
+    }
+
+    links
+}
+
+/// Check `links` through `checker` with bounded concurrency, and compare the
+/// result against the set of hrefs last seen and last known broken on
+/// `page_url` (stored in `store`), reporting newly-appeared links plus
+/// broken/fixed links on the run where each state transition happens - a
+/// link that's still down from a previous run isn't reported again.
+pub(crate) async fn check_links<L: LinkChecker, S: Store>(
+    checker: &L,
+    store: &S,
+    page_url: &str,
+    links: &[String],
+) -> LinkReport {
+    let seen_key = kv_key(page_url, KV_LINKS_SUFFIX);
+    let previously_seen: HashSet<String> = match store.get(&seen_key).await {
+        Ok(v) => v.map(|s| s.lines().map(String::from).collect()).unwrap_or_default(),
+        Err(e) => {
+            console_error(&format!("{}: KV get {} failed: {}", page_url, seen_key, e));
+            HashSet::new()
+        }
+    };
+
+    let broken_key = kv_key(page_url, KV_BROKEN_LINKS_SUFFIX);
+    let previously_broken: HashSet<String> = match store.get(&broken_key).await {
+        Ok(v) => v.map(|s| s.lines().map(String::from).collect()).unwrap_or_default(),
+        Err(e) => {
+            console_error(&format!("{}: KV get {} failed: {}", page_url, broken_key, e));
+            HashSet::new()
+        }
+    };
+
+    let mut currently_broken = Vec::new();
+    for chunk in links.chunks(LINK_CONCURRENCY_LIMIT) {
+        let checks = chunk.iter().map(|link| checker.check(link));
+        let outcomes = join_all(checks).await;
+        for (link, outcome) in chunk.iter().zip(outcomes) {
+            match outcome {
+                LinkOutcome::HttpError { status, location } => {
+                    currently_broken.push((link.clone(), format!("HTTP {} at {}", status, location)));
+                }
+                LinkOutcome::TransportError(e) => {
+                    currently_broken.push((link.clone(), e));
+                }
+                LinkOutcome::Ok { .. } => {}
+            }
+        }
+    }
+
+    let currently_broken_set: HashSet<String> = currently_broken.iter().map(|(link, _)| link.clone()).collect();
+    let broken: Vec<(String, String)> = currently_broken
+        .into_iter()
+        .filter(|(link, _)| !previously_broken.contains(link))
+        .collect();
+    let fixed: Vec<String> = previously_broken
+        .iter()
+        .filter(|link| !currently_broken_set.contains(*link))
+        .cloned()
+        .collect();
+
+    let new_links: Vec<String> = links
+        .iter()
+        .filter(|link| !previously_seen.contains(*link))
+        .cloned()
+        .collect();
+
+    let current_seen: HashSet<String> = links.iter().cloned().collect();
+    if current_seen != previously_seen {
+        if let Err(e) = store.put(&seen_key, &links.join("\n")).await {
+            console_error(&format!("{}: KV put {} failed: {}", page_url, seen_key, e));
+        }
+    }
+
+    if currently_broken_set != previously_broken {
+        let joined: Vec<&str> = currently_broken_set.iter().map(String::as_str).collect();
+        if let Err(e) = store.put(&broken_key, &joined.join("\n")).await {
+            console_error(&format!("{}: KV put {} failed: {}", page_url, broken_key, e));
+        }
+    }
+
+    LinkReport { broken, new_links, fixed }
+}
+
+/// Render a page's link report as a notification-ready line, or `None` when
+/// nothing noteworthy happened to its links this run.
+pub(crate) fn format_link_report(page_url: &str, report: &LinkReport) -> Option<String> {
+    if report.broken.is_empty() && report.new_links.is_empty() && report.fixed.is_empty() {
+        return None;
+    }
+
+    let mut parts = Vec::new();
+    if !report.broken.is_empty() {
+        let broken: Vec<String> = report
+            .broken
+            .iter()
+            .map(|(url, reason)| format!("{} ({})", url, reason))
+            .collect();
+        parts.push(format!("broken: {}", broken.join(", ")));
+    }
+    if !report.new_links.is_empty() {
+        parts.push(format!("new: {}", report.new_links.join(", ")));
+    }
+    if !report.fixed.is_empty() {
+        parts.push(format!("fixed: {}", report.fixed.join(", ")));
+    }
+
+    Some(format!("Links on {}: {}", page_url, parts.join("; ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MockStore(RefCell<HashMap<String, String>>);
+
+    #[async_trait::async_trait(?Send)]
+    impl Store for MockStore {
+        async fn get(&self, key: &str) -> Result<Option<String>, String> {
+            Ok(self.0.borrow().get(key).cloned())
+        }
+
+        async fn put(&self, key: &str, value: &str) -> Result<(), String> {
+            self.0.borrow_mut().insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    struct ScriptedChecker(HashMap<String, LinkOutcome>);
+
+    #[async_trait::async_trait(?Send)]
+    impl LinkChecker for ScriptedChecker {
+        async fn check(&self, url: &str) -> LinkOutcome {
+            match self.0.get(url) {
+                Some(LinkOutcome::Ok { status, location }) => LinkOutcome::Ok {
+                    status: *status,
+                    location: location.clone(),
+                },
+                Some(LinkOutcome::HttpError { status, location }) => LinkOutcome::HttpError {
+                    status: *status,
+                    location: location.clone(),
+                },
+                Some(LinkOutcome::TransportError(e)) => LinkOutcome::TransportError(e.clone()),
+                None => LinkOutcome::Ok {
+                    status: 200,
+                    location: url.to_string(),
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn extracts_and_resolves_relative_links() {
+        let html = r#"<html><body>
+            <a href="/register">Register</a>
+            <a href="https://other.example/info">Info</a>
+            <a href="/register">Duplicate</a>
+        </body></html>"#;
+        let links = extract_links(html, "https://www.ucd.ie/japan/exams/");
+        assert_eq!(
+            links,
+            vec![
+                "https://www.ucd.ie/register".to_string(),
+                "https://other.example/info".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_non_http_schemes() {
+        let html = r#"<html><body>
+            <a href="/register">Register</a>
+            <a href="mailto:exams@ucd.ie">Email</a>
+            <a href="tel:+35312345678">Call</a>
+            <a href="javascript:void(0)">Nothing</a>
+        </body></html>"#;
+        let links = extract_links(html, "https://www.ucd.ie/japan/exams/");
+        assert_eq!(links, vec!["https://www.ucd.ie/register".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn reports_broken_and_new_links() {
+        let store = MockStore::default();
+        let page = "https://www.ucd.ie/japan/exams/";
+
+        let first_links = vec!["https://www.ucd.ie/register".to_string()];
+        let checker = ScriptedChecker(HashMap::new());
+        let report = check_links(&checker, &store, page, &first_links).await;
+        assert!(report.broken.is_empty());
+        assert_eq!(report.new_links, first_links);
+
+        let mut outcomes = HashMap::new();
+        outcomes.insert(
+            "https://www.ucd.ie/register".to_string(),
+            LinkOutcome::HttpError {
+                status: 404,
+                location: "https://www.ucd.ie/register".to_string(),
+            },
+        );
+        let checker = ScriptedChecker(outcomes.clone());
+        let second_links = vec![
+            "https://www.ucd.ie/register".to_string(),
+            "https://www.ucd.ie/new-center".to_string(),
+        ];
+        let report = check_links(&checker, &store, page, &second_links).await;
+        assert_eq!(report.broken, vec![(
+            "https://www.ucd.ie/register".to_string(),
+            "HTTP 404 at https://www.ucd.ie/register".to_string()
+        )]);
+        assert_eq!(report.new_links, vec!["https://www.ucd.ie/new-center".to_string()]);
+        assert!(report.fixed.is_empty());
+
+        // The link is still down on the next run - already reported once,
+        // so it shouldn't be reported again.
+        let checker = ScriptedChecker(outcomes);
+        let report = check_links(&checker, &store, page, &second_links).await;
+        assert!(report.broken.is_empty());
+        assert!(report.new_links.is_empty());
+        assert!(report.fixed.is_empty());
+
+        // The link recovers - reported once, in `fixed`.
+        let checker = ScriptedChecker(HashMap::new());
+        let report = check_links(&checker, &store, page, &second_links).await;
+        assert!(report.broken.is_empty());
+        assert!(report.new_links.is_empty());
+        assert_eq!(report.fixed, vec!["https://www.ucd.ie/register".to_string()]);
+    }
+
+    #[test]
+    fn format_link_report_renders_broken_and_new_links() {
+        let report = LinkReport {
+            broken: vec![(
+                "https://www.ucd.ie/register".to_string(),
+                "HTTP 404 at https://www.ucd.ie/register".to_string(),
+            )],
+            new_links: vec!["https://www.ucd.ie/new-center".to_string()],
+            fixed: vec!["https://www.ucd.ie/old-center".to_string()],
+        };
+        let line = format_link_report("https://www.ucd.ie/japan/exams/", &report).unwrap();
+        assert_eq!(
+            line,
+            "Links on https://www.ucd.ie/japan/exams/: \
+             broken: https://www.ucd.ie/register (HTTP 404 at https://www.ucd.ie/register); \
+             new: https://www.ucd.ie/new-center; \
+             fixed: https://www.ucd.ie/old-center"
+        );
+    }
+
+    #[test]
+    fn format_link_report_is_none_when_nothing_changed() {
+        let report = LinkReport::default();
+        assert!(format_link_report("https://www.ucd.ie/japan/exams/", &report).is_none());
+    }
+}